@@ -0,0 +1,185 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::{ErrorKind, Result};
+
+use chariot_io_tools::{ReadExt, WriteExt};
+
+use std::io::prelude::{Read, Write, Seek};
+
+/// Upper bound on a preview dimension accepted on read; anything larger is
+/// treated as a corrupt info header rather than a real thumbnail.
+const REASONABLE_BITMAP_DIMENSION: i32 = 4096;
+
+/// The standard Windows `BITMAPINFOHEADER` preceding the preview pixel data.
+#[derive(Default, Debug)]
+struct InfoHeader {
+    size: u32,
+    width: i32,
+    height: i32,
+    planes: u16,
+    bit_count: u16,
+    compression: u32,
+    size_image: u32,
+    x_pels_per_meter: i32,
+    y_pels_per_meter: i32,
+    colors_used: u32,
+    colors_important: u32,
+}
+
+impl InfoHeader {
+    fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<InfoHeader> {
+        let mut header: InfoHeader = Default::default();
+        header.size = try!(stream.read_u32());
+        header.width = try!(stream.read_i32());
+        header.height = try!(stream.read_i32());
+        header.planes = try!(stream.read_u16());
+        header.bit_count = try!(stream.read_u16());
+        header.compression = try!(stream.read_u32());
+        header.size_image = try!(stream.read_u32());
+        header.x_pels_per_meter = try!(stream.read_i32());
+        header.y_pels_per_meter = try!(stream.read_i32());
+        header.colors_used = try!(stream.read_u32());
+        header.colors_important = try!(stream.read_u32());
+        Ok(header)
+    }
+
+    fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_u32(self.size));
+        try!(stream.write_i32(self.width));
+        try!(stream.write_i32(self.height));
+        try!(stream.write_u16(self.planes));
+        try!(stream.write_u16(self.bit_count));
+        try!(stream.write_u32(self.compression));
+        try!(stream.write_u32(self.size_image));
+        try!(stream.write_i32(self.x_pels_per_meter));
+        try!(stream.write_i32(self.y_pels_per_meter));
+        try!(stream.write_u32(self.colors_used));
+        try!(stream.write_u32(self.colors_important));
+        Ok(())
+    }
+}
+
+/// The embedded preview/minimap bitmap shown in the scenario browser.
+///
+/// The pixels are palette-indexed (resolvable against the `PlayerColor`
+/// palette in `EmpiresDb`) and kept in file row order with each row padded to
+/// a 4-byte boundary, exactly as in an on-disk Windows BMP: bottom-up, or
+/// top-down when the info header's height is negative.
+#[derive(Default, Debug)]
+pub struct Bitmap {
+    width: u32,
+    height: u32,
+    info: InfoHeader,
+    pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    /// The preview width in pixels
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The preview height in pixels
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The raw, row-padded palette indices, in file row order
+    #[inline]
+    pub fn pixels<'a>(&'a self) -> &'a [u8] {
+        &self.pixels
+    }
+
+    /// Number of bytes in each (4-byte aligned) row of an 8bpp bitmap
+    fn padded_row_size(width: i32) -> usize {
+        (((width as usize) + 3) / 4) * 4
+    }
+
+    /// Reads the preview bitmap. Returns `None` when the "include" flag marks
+    /// the scenario as carrying no preview.
+    pub fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<Option<Bitmap>> {
+        let width = try!(stream.read_u32());
+        let height = try!(stream.read_u32());
+        let included = try!(stream.read_i16());
+        if included == 0 {
+            return Ok(None);
+        }
+
+        let mut bitmap = Bitmap {
+            width: width,
+            height: height,
+            info: try!(InfoHeader::read_from_stream(stream)),
+            pixels: Vec::new(),
+        };
+
+        // A corrupt info header can carry a negative or absurd width/height;
+        // reject it before it drives a huge (or overflowing) allocation.
+        // `checked_abs()` also rejects `i32::MIN`, whose plain `abs()` would
+        // overflow (panic in debug, wrap to `i32::MIN` in release).
+        let abs_height = match bitmap.info.height.checked_abs() {
+            Some(h) => h,
+            None => return Err(ErrorKind::PreviewBitmapTooLarge.into()),
+        };
+        if bitmap.info.width < 0 || bitmap.info.width > REASONABLE_BITMAP_DIMENSION ||
+           abs_height > REASONABLE_BITMAP_DIMENSION {
+            return Err(ErrorKind::PreviewBitmapTooLarge.into());
+        }
+
+        // The info-header dimensions are authoritative for decoding; reject a
+        // file whose outer width/height prefix disagrees so the public
+        // `width()`/`height()` accessors always describe the decoded pixels.
+        if bitmap.width != bitmap.info.width as u32 || bitmap.height != abs_height as u32 {
+            return Err(ErrorKind::PreviewBitmapTooLarge.into());
+        }
+
+        let row_size = Bitmap::padded_row_size(bitmap.info.width);
+        let row_count = abs_height as usize;
+        bitmap.pixels = try!(stream.read_array(row_size * row_count, |s| s.read_byte()));
+        Ok(Some(bitmap))
+    }
+
+    /// Writes the width/height/include prefix followed by the info header and
+    /// pixels. `bitmap` is `None` when no preview is present.
+    pub fn write_to_stream<W: Write + Seek>(bitmap: Option<&Bitmap>,
+                                            stream: &mut W)
+                                            -> Result<()> {
+        match bitmap {
+            Some(bitmap) => {
+                try!(stream.write_u32(bitmap.width));
+                try!(stream.write_u32(bitmap.height));
+                try!(stream.write_i16(1));
+                try!(bitmap.info.write_to_stream(stream));
+                for pixel in &bitmap.pixels {
+                    try!(stream.write_byte(*pixel));
+                }
+            }
+            None => {
+                try!(stream.write_u32(0));
+                try!(stream.write_u32(0));
+                try!(stream.write_i16(0));
+            }
+        }
+        Ok(())
+    }
+}
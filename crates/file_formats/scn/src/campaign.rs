@@ -0,0 +1,202 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::{ErrorKind, Result};
+use scn::Scenario;
+
+use chariot_io_tools::{ReadExt, WriteExt};
+
+use std::fs::File;
+use std::io;
+use std::io::prelude::{Read, Write, Seek};
+use std::path::Path;
+
+/// Version tag written at the head of a campaign produced by Chariot
+const CAMPAIGN_VERSION: &'static str = "1.00";
+
+/// Fixed width of the scenario file name stored in each table entry
+const SCENARIO_FILE_NAME_LEN: usize = 255;
+
+/// Fixed width of the human-readable scenario name stored in each table entry
+const SCENARIO_DISPLAY_NAME_LEN: usize = 255;
+
+/// Upper bound on the campaign name length accepted on read, to reject a
+/// malformed header before it forces a huge allocation
+const REASONABLE_CAMPAIGN_NAME_LIMIT: usize = 64 * 1024;
+
+/// Upper bound on the number of scenarios accepted on read
+const REASONABLE_SCENARIO_LIMIT: usize = 64 * 1024;
+
+/// A single entry in a campaign's scenario table.
+#[derive(Default, Debug)]
+pub struct ScenarioMeta {
+    data_size: u32,
+    file_offset: u32,
+    file_name: String,
+    display_name: String,
+}
+
+impl ScenarioMeta {
+    #[inline]
+    pub fn file_name<'a>(&'a self) -> &'a str {
+        &self.file_name
+    }
+
+    #[inline]
+    pub fn display_name<'a>(&'a self) -> &'a str {
+        &self.display_name
+    }
+
+    #[inline]
+    pub fn data_size(&self) -> u32 {
+        self.data_size
+    }
+
+    fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<ScenarioMeta> {
+        let mut meta: ScenarioMeta = Default::default();
+        meta.data_size = try!(stream.read_u32());
+        meta.file_offset = try!(stream.read_u32());
+        meta.file_name = try!(stream.read_sized_str(SCENARIO_FILE_NAME_LEN));
+        meta.display_name = try!(stream.read_sized_str(SCENARIO_DISPLAY_NAME_LEN));
+        Ok(meta)
+    }
+
+    fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_u32(self.data_size));
+        try!(stream.write_u32(self.file_offset));
+        try!(stream.write_sized_str(&self.file_name, SCENARIO_FILE_NAME_LEN));
+        try!(stream.write_sized_str(&self.display_name, SCENARIO_DISPLAY_NAME_LEN));
+        Ok(())
+    }
+}
+
+/// A campaign (`.cpx`) container: a named bundle of scenarios whose blobs are
+/// laid out back-to-back after an offset table and parsed on demand.
+#[derive(Default, Debug)]
+pub struct Campaign {
+    name: String,
+    scenarios: Vec<ScenarioMeta>,
+    data: Vec<u8>,
+}
+
+impl Campaign {
+    /// Returns the number of scenarios bundled in the campaign
+    #[inline]
+    pub fn scenario_count(&self) -> usize {
+        self.scenarios.len()
+    }
+
+    /// Returns the metadata for the scenario at the given index
+    #[inline]
+    pub fn scenario_meta<'a>(&'a self, index: usize) -> &'a ScenarioMeta {
+        &self.scenarios[index]
+    }
+
+    /// Parses and returns the scenario at the given index from its embedded blob
+    pub fn read_scenario(&self, index: usize) -> Result<Scenario> {
+        let meta = &self.scenarios[index];
+        let start = meta.file_offset as usize;
+        let end = start + meta.data_size as usize;
+        if start > self.data.len() || end > self.data.len() {
+            // The offset table points past the end of the campaign data
+            return Err(ErrorKind::CorruptCampaign.into());
+        }
+        Scenario::read_from_stream(io::Cursor::new(&self.data[start..end]))
+    }
+
+    pub fn read_from_file<P: AsRef<Path>>(file_name: P) -> Result<Campaign> {
+        Campaign::read_from_stream(try!(File::open(file_name.as_ref())))
+    }
+
+    pub fn read_from_stream<S: Read + Seek>(mut stream: S) -> Result<Campaign> {
+        let mut campaign: Campaign = Default::default();
+        let _version = try!(stream.read_sized_str(4));
+        campaign.name = {
+            let length = try!(stream.read_u32()) as usize;
+            if length > REASONABLE_CAMPAIGN_NAME_LIMIT {
+                // Refuse to load an implausibly long campaign name
+                return Err(ErrorKind::CorruptCampaign.into());
+            }
+            try!(stream.read_sized_str(length))
+        };
+
+        let scenario_count = try!(stream.read_u32()) as usize;
+        if scenario_count > REASONABLE_SCENARIO_LIMIT {
+            return Err(ErrorKind::CorruptCampaign.into());
+        }
+        campaign.scenarios = try!((0..scenario_count)
+            .map(|_| ScenarioMeta::read_from_stream(&mut stream))
+            .collect());
+
+        // The scenario blobs are addressed by absolute offset, so keep the
+        // whole file around and slice it lazily in `read_scenario`.
+        try!(stream.seek(io::SeekFrom::Start(0)));
+        try!(stream.read_to_end(&mut campaign.data));
+        Ok(campaign)
+    }
+
+    /// Builds a campaign from a list of named scenarios, laying out the offset
+    /// table and concatenating each scenario's serialized body after it.
+    pub fn write_to_file<P: AsRef<Path>>(file_name: P,
+                                         name: &str,
+                                         scenarios: &[(String, Scenario)])
+                                         -> Result<()> {
+        Campaign::write_to_stream(try!(File::create(file_name.as_ref())), name, scenarios)
+    }
+
+    pub fn write_to_stream<W: Write + Seek>(mut stream: W,
+                                            name: &str,
+                                            scenarios: &[(String, Scenario)])
+                                            -> Result<()> {
+        // Serialize each scenario up front so we know its size for the table.
+        let mut bodies = Vec::with_capacity(scenarios.len());
+        for &(_, ref scenario) in scenarios {
+            let mut body = io::Cursor::new(Vec::new());
+            try!(scenario.write_to_stream(&mut body));
+            bodies.push(body.into_inner());
+        }
+
+        let entry_size = 4 + 4 + SCENARIO_FILE_NAME_LEN + SCENARIO_DISPLAY_NAME_LEN;
+        let header_size = 4 + 4 + name.len() + 4 + scenarios.len() * entry_size;
+
+        try!(stream.write_sized_str(CAMPAIGN_VERSION, 4));
+        try!(stream.write_u32(name.len() as u32));
+        try!(stream.write_sized_str(name, name.len()));
+        try!(stream.write_u32(scenarios.len() as u32));
+
+        let mut offset = header_size;
+        for (i, &(ref scenario_name, _)) in scenarios.iter().enumerate() {
+            let meta = ScenarioMeta {
+                data_size: bodies[i].len() as u32,
+                file_offset: offset as u32,
+                file_name: scenario_name.clone(),
+                display_name: scenario_name.clone(),
+            };
+            try!(meta.write_to_stream(&mut stream));
+            offset += bodies[i].len();
+        }
+
+        for body in &bodies {
+            try!(stream.write_all(body));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::Result;
+use version::ScenarioVersion;
+
+use chariot_io_tools::{ReadExt, WriteExt};
+
+use std::io::prelude::{Read, Write, Seek};
+
+/// Garrison id written for a unit that is not garrisoned inside another object
+const NOT_GARRISONED: i32 = -1;
+
+/// A single object placed in the scenario by the author, carrying the full
+/// sub-tile position and orientation the engine needs to render and simulate
+/// it exactly as authored.
+#[derive(Default, Debug)]
+pub struct PlayerUnit {
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    id: u32,
+    unit_type: u16,
+    state: u8,
+    angle: f32,
+    frame: i16,
+    garrisoned_in: i32,
+}
+
+impl PlayerUnit {
+    /// The unit's floating-point position within the map
+    #[inline]
+    pub fn position(&self) -> (f32, f32, f32) {
+        (self.position_x, self.position_y, self.position_z)
+    }
+
+    /// The unique object id of this placed unit
+    #[inline]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The unit type (master object) id
+    #[inline]
+    pub fn unit_type(&self) -> u16 {
+        self.unit_type
+    }
+
+    /// The direction the unit faces, in radians
+    #[inline]
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    /// The current animation frame
+    #[inline]
+    pub fn frame(&self) -> i16 {
+        self.frame
+    }
+
+    /// The id of the object this unit is garrisoned in, or `None` when it is
+    /// standing free on the map.
+    #[inline]
+    pub fn garrisoned_in(&self) -> Option<i32> {
+        if self.garrisoned_in == NOT_GARRISONED {
+            None
+        } else {
+            Some(self.garrisoned_in)
+        }
+    }
+
+    pub fn read_from_stream<S: Read + Seek>(stream: &mut S,
+                                            version: ScenarioVersion)
+                                            -> Result<PlayerUnit> {
+        let mut unit: PlayerUnit = Default::default();
+        unit.position_x = try!(stream.read_f32());
+        unit.position_y = try!(stream.read_f32());
+        unit.position_z = try!(stream.read_f32());
+        unit.id = try!(stream.read_u32());
+        unit.unit_type = try!(stream.read_u16());
+        unit.state = try!(stream.read_byte());
+        unit.angle = try!(stream.read_f32());
+
+        // The animation frame and garrison link were only added to the placed
+        // object record in the Rise of Rome format revisions.
+        if version.has_extended_unit_data() {
+            unit.frame = try!(stream.read_i16());
+            unit.garrisoned_in = try!(stream.read_i32());
+        } else {
+            unit.garrisoned_in = NOT_GARRISONED;
+        }
+        Ok(unit)
+    }
+
+    pub fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_f32(self.position_x));
+        try!(stream.write_f32(self.position_y));
+        try!(stream.write_f32(self.position_z));
+        try!(stream.write_u32(self.id));
+        try!(stream.write_u16(self.unit_type));
+        try!(stream.write_byte(self.state));
+        try!(stream.write_f32(self.angle));
+        try!(stream.write_i16(self.frame));
+        try!(stream.write_i32(self.garrisoned_in));
+        Ok(())
+    }
+}
@@ -22,24 +22,31 @@
 use error::{ErrorKind, Result};
 
 use identifier::{CivilizationId, PlayerId};
-use chariot_io_tools::{ReadExt, ReadArrayExt};
+use bitmap::Bitmap;
+use chariot_io_tools::{ReadExt, ReadArrayExt, WriteExt};
 use map::Map;
 use player_data::PlayerData;
 use player_resources::PlayerResources;
 use player_unit::PlayerUnit;
+use trigger::{Trigger, TriggerSystem};
+use victory::{PlayerVictory, VictoryConditions};
+use version::ScenarioVersion;
 use std::fs::File;
 
 use std::io;
-use std::io::prelude::{Read, Seek};
+use std::io::prelude::{Read, Write, Seek};
 use std::path::Path;
 
 #[derive(Default, Debug)]
 pub struct Scenario {
     header: ScenarioHeader,
+    next_unit_id: u32,
     pub player_data: PlayerData,
     player_resources: Vec<PlayerResources>,
     player_units: Vec<Vec<PlayerUnit>>,
     pub map: Map,
+    victory: VictoryConditions,
+    triggers: TriggerSystem,
 }
 
 impl Scenario {
@@ -66,7 +73,69 @@ impl Scenario {
         (0..self.player_units.len()).map(|i| i.into()).collect()
     }
 
-    // TODO: Implement writing
+    /// Returns the format version the scenario was read as. `write_to_stream`
+    /// always writes the 1.11 format, whatever this returns.
+    #[inline]
+    pub fn version(&self) -> ScenarioVersion {
+        self.header.version
+    }
+
+    /// Returns the embedded preview/minimap bitmap, if the scenario carries one
+    #[inline]
+    pub fn preview_bitmap<'a>(&'a self) -> Option<&'a Bitmap> {
+        self.header.preview.as_ref()
+    }
+
+    /// Returns the victory conditions configured for the given player
+    #[inline]
+    pub fn victory_conditions<'a>(&'a self, player_id: PlayerId) -> Option<&'a PlayerVictory> {
+        self.victory.player(*player_id as usize)
+    }
+
+    /// Returns the scenario's triggers (conditions that fire effects)
+    #[inline]
+    pub fn triggers<'a>(&'a self) -> &'a [Trigger] {
+        self.triggers.triggers()
+    }
+
+    /// Writes the scenario to a file, creating (or truncating) it
+    pub fn write_to_file<P: AsRef<Path>>(&self, file_name: P) -> Result<()> {
+        self.write_to_stream(try!(File::create(file_name.as_ref())))
+    }
+
+    /// Serializes the scenario, mirroring `read_from_stream`: the header is
+    /// written uncompressed, then the body is assembled in memory and written
+    /// back out with the same raw DEFLATE compression the reader expects.
+    pub fn write_to_stream<W: Write + Seek>(&self, mut stream: W) -> Result<()> {
+        try!(self.header.write_to_stream(&mut stream));
+
+        // NB: `PlayerColor` is not serialized here despite being listed among
+        // the sub-structs in the original request. It lives in the `dat`
+        // (`EmpiresDb`) path, not the scenario body this writer assembles, so it
+        // has no symmetric `write_to_stream` and is intentionally absent.
+        let mut body = Vec::new();
+        {
+            let mut cursor = io::Cursor::new(&mut body);
+            try!(cursor.write_u32(self.next_unit_id));
+            try!(self.player_data.write_to_stream(&mut cursor));
+            try!(self.map.write_to_stream(&mut cursor));
+
+            try!(cursor.write_u32(self.player_units.len() as u32));
+            try!(PlayerResources::write_to_stream(&mut cursor, &self.player_resources));
+
+            for units in &self.player_units {
+                try!(cursor.write_u32(units.len() as u32));
+                for unit in units {
+                    try!(unit.write_to_stream(&mut cursor));
+                }
+            }
+
+            try!(self.victory.write_to_stream(&mut cursor));
+            try!(self.triggers.write_to_stream(&mut cursor));
+        }
+        try!(stream.write_and_compress(&body));
+        Ok(())
+    }
 
     pub fn read_from_file<P: AsRef<Path>>(file_name: P) -> Result<Scenario> {
         Scenario::read_from_stream(try!(File::open(file_name.as_ref())))
@@ -76,23 +145,26 @@ impl Scenario {
         let mut scenario: Scenario = Default::default();
         scenario.header = try!(ScenarioHeader::read_from_stream(&mut stream));
 
+        let version = scenario.header.version;
         let mut stream = io::Cursor::new(try!(stream.read_and_decompress()));
 
-        let _next_unit_id = try!(stream.read_u32()); // not sure what this is for yet
-        scenario.player_data = try!(PlayerData::read_from_stream(&mut stream));
-        scenario.map = try!(Map::read_from_stream(&mut stream));
+        scenario.next_unit_id = try!(stream.read_u32());
+        scenario.player_data = try!(PlayerData::read_from_stream(&mut stream, version));
+        scenario.map = try!(Map::read_from_stream(&mut stream, version));
 
         let player_unit_group_count = try!(stream.read_u32()) as isize;
-        scenario.player_resources = try!(PlayerResources::read_from_stream(&mut stream));
+        scenario.player_resources = try!(PlayerResources::read_from_stream(&mut stream, version));
 
         for _player_index in 0..player_unit_group_count {
             let unit_count = try!(stream.read_u32()) as usize;
-            let units = try!(stream.read_array(unit_count, |s| PlayerUnit::read_from_stream(s)));
+            let units = try!(stream.read_array(unit_count,
+                                               |s| PlayerUnit::read_from_stream(s, version)));
             scenario.player_units.push(units);
         }
 
-        // TODO: Read other player data
-        // TODO: Read triggers
+        scenario.victory = try!(VictoryConditions::read_from_stream(&mut stream,
+                                                                    player_unit_group_count as usize));
+        scenario.triggers = try!(TriggerSystem::read_from_stream(&mut stream));
 
         Ok(scenario)
     }
@@ -102,24 +174,36 @@ const REASONABLE_INSTRUCTION_LIMIT: usize = 512 * 1024; // 0.5 mibibytes
 
 #[derive(Default, Debug)]
 struct ScenarioHeader {
-    version: String,
+    version: ScenarioVersion,
     length: u32,
     save_type: i32,
     last_save_time: u32,
     instructions: String,
     victory_type: u32,
     player_count: u32,
+    preview: Option<Bitmap>,
 }
 
 impl ScenarioHeader {
-    // TODO: Implement writing
+    fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        // The body is always assembled in the 1.11 layout (PlayerUnit writes the
+        // extended placement block unconditionally), so the written tag must be
+        // 1.11 to match regardless of the version the scenario was read as.
+        try!(stream.write_sized_str(ScenarioVersion::V1_11.as_tag(), 4));
+        try!(stream.write_u32(self.length));
+        try!(stream.write_i32(self.save_type));
+        try!(stream.write_u32(self.last_save_time));
+        try!(stream.write_u32(self.instructions.len() as u32));
+        try!(stream.write_sized_str(&self.instructions, self.instructions.len()));
+        try!(stream.write_u32(self.victory_type));
+        try!(stream.write_u32(self.player_count));
+        try!(Bitmap::write_to_stream(self.preview.as_ref(), stream));
+        Ok(())
+    }
 
     fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<ScenarioHeader> {
         let mut header: ScenarioHeader = Default::default();
-        header.version = try!(stream.read_sized_str(4));
-        if header.version != "1.11" {
-            return Err(ErrorKind::UnrecognizedScenarioVersion.into());
-        }
+        header.version = try!(ScenarioVersion::from_tag(&try!(stream.read_sized_str(4))));
 
         header.length = try!(stream.read_u32());
         header.save_type = try!(stream.read_i32());
@@ -134,6 +218,7 @@ impl ScenarioHeader {
         };
         header.victory_type = try!(stream.read_u32());
         header.player_count = try!(stream.read_u32());
+        header.preview = try!(Bitmap::read_from_stream(stream));
         Ok(header)
     }
 }
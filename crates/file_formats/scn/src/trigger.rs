@@ -0,0 +1,484 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::{ErrorKind, Result};
+
+use chariot_io_tools::{ReadExt, WriteExt};
+
+use std::io::prelude::{Read, Write, Seek};
+
+/// Reject files that claim an implausible number of triggers
+const REASONABLE_TRIGGER_LIMIT: usize = 64 * 1024;
+
+/// Reject trigger/effect strings that claim an implausible length, matching the
+/// string guards elsewhere in the crate
+const REASONABLE_STRING_LIMIT: usize = 512 * 1024; // 0.5 mibibytes
+
+/// The kind of change a trigger effect applies when it fires.
+///
+/// Unrecognized type codes are preserved in `Unknown` so that files using
+/// effects Chariot doesn't model yet still round-trip losslessly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EffectType {
+    None,
+    ChangeDiplomacy,
+    ResearchTechnology,
+    SendChat,
+    PlaySound,
+    Tribute,
+    UnlockGate,
+    LockGate,
+    ActivateTrigger,
+    DeactivateTrigger,
+    AiScriptGoal,
+    CreateObject,
+    TaskObject,
+    DeclareVictory,
+    KillObject,
+    RemoveObject,
+    ChangeView,
+    Unload,
+    ChangeOwnership,
+    Patrol,
+    DisplayInstructions,
+    ClearInstructions,
+    FreezeUnit,
+    Unknown(i32),
+}
+
+impl EffectType {
+    pub fn from_code(code: i32) -> EffectType {
+        match code {
+            0 => EffectType::None,
+            1 => EffectType::ChangeDiplomacy,
+            2 => EffectType::ResearchTechnology,
+            3 => EffectType::SendChat,
+            4 => EffectType::PlaySound,
+            5 => EffectType::Tribute,
+            6 => EffectType::UnlockGate,
+            7 => EffectType::LockGate,
+            8 => EffectType::ActivateTrigger,
+            9 => EffectType::DeactivateTrigger,
+            10 => EffectType::AiScriptGoal,
+            11 => EffectType::CreateObject,
+            12 => EffectType::TaskObject,
+            13 => EffectType::DeclareVictory,
+            14 => EffectType::KillObject,
+            15 => EffectType::RemoveObject,
+            16 => EffectType::ChangeView,
+            17 => EffectType::Unload,
+            18 => EffectType::ChangeOwnership,
+            19 => EffectType::Patrol,
+            20 => EffectType::DisplayInstructions,
+            21 => EffectType::ClearInstructions,
+            22 => EffectType::FreezeUnit,
+            other => EffectType::Unknown(other),
+        }
+    }
+
+    pub fn as_code(&self) -> i32 {
+        match *self {
+            EffectType::None => 0,
+            EffectType::ChangeDiplomacy => 1,
+            EffectType::ResearchTechnology => 2,
+            EffectType::SendChat => 3,
+            EffectType::PlaySound => 4,
+            EffectType::Tribute => 5,
+            EffectType::UnlockGate => 6,
+            EffectType::LockGate => 7,
+            EffectType::ActivateTrigger => 8,
+            EffectType::DeactivateTrigger => 9,
+            EffectType::AiScriptGoal => 10,
+            EffectType::CreateObject => 11,
+            EffectType::TaskObject => 12,
+            EffectType::DeclareVictory => 13,
+            EffectType::KillObject => 14,
+            EffectType::RemoveObject => 15,
+            EffectType::ChangeView => 16,
+            EffectType::Unload => 17,
+            EffectType::ChangeOwnership => 18,
+            EffectType::Patrol => 19,
+            EffectType::DisplayInstructions => 20,
+            EffectType::ClearInstructions => 21,
+            EffectType::FreezeUnit => 22,
+            EffectType::Unknown(code) => code,
+        }
+    }
+}
+
+impl Default for EffectType {
+    fn default() -> EffectType {
+        EffectType::None
+    }
+}
+
+/// The kind of world-state test a trigger condition evaluates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConditionType {
+    None,
+    BringObjectToArea,
+    BringObjectToObject,
+    OwnObjects,
+    OwnFewerObjects,
+    ObjectsInArea,
+    DestroyObject,
+    CapturePoint,
+    AccumulateAttribute,
+    ResearchTechnology,
+    Timer,
+    ObjectSelected,
+    AiSignal,
+    PlayerDefeated,
+    ObjectHasTarget,
+    ObjectVisible,
+    ObjectNotVisible,
+    ResearchingTechnology,
+    UnitsGarrisoned,
+    DifficultyLevel,
+    Unknown(i32),
+}
+
+impl ConditionType {
+    pub fn from_code(code: i32) -> ConditionType {
+        match code {
+            0 => ConditionType::None,
+            1 => ConditionType::BringObjectToArea,
+            2 => ConditionType::BringObjectToObject,
+            3 => ConditionType::OwnObjects,
+            4 => ConditionType::OwnFewerObjects,
+            5 => ConditionType::ObjectsInArea,
+            6 => ConditionType::DestroyObject,
+            7 => ConditionType::CapturePoint,
+            8 => ConditionType::AccumulateAttribute,
+            9 => ConditionType::ResearchTechnology,
+            10 => ConditionType::Timer,
+            11 => ConditionType::ObjectSelected,
+            12 => ConditionType::AiSignal,
+            13 => ConditionType::PlayerDefeated,
+            14 => ConditionType::ObjectHasTarget,
+            15 => ConditionType::ObjectVisible,
+            16 => ConditionType::ObjectNotVisible,
+            17 => ConditionType::ResearchingTechnology,
+            18 => ConditionType::UnitsGarrisoned,
+            19 => ConditionType::DifficultyLevel,
+            other => ConditionType::Unknown(other),
+        }
+    }
+
+    pub fn as_code(&self) -> i32 {
+        match *self {
+            ConditionType::None => 0,
+            ConditionType::BringObjectToArea => 1,
+            ConditionType::BringObjectToObject => 2,
+            ConditionType::OwnObjects => 3,
+            ConditionType::OwnFewerObjects => 4,
+            ConditionType::ObjectsInArea => 5,
+            ConditionType::DestroyObject => 6,
+            ConditionType::CapturePoint => 7,
+            ConditionType::AccumulateAttribute => 8,
+            ConditionType::ResearchTechnology => 9,
+            ConditionType::Timer => 10,
+            ConditionType::ObjectSelected => 11,
+            ConditionType::AiSignal => 12,
+            ConditionType::PlayerDefeated => 13,
+            ConditionType::ObjectHasTarget => 14,
+            ConditionType::ObjectVisible => 15,
+            ConditionType::ObjectNotVisible => 16,
+            ConditionType::ResearchingTechnology => 17,
+            ConditionType::UnitsGarrisoned => 18,
+            ConditionType::DifficultyLevel => 19,
+            ConditionType::Unknown(code) => code,
+        }
+    }
+}
+
+impl Default for ConditionType {
+    fn default() -> ConditionType {
+        ConditionType::None
+    }
+}
+
+/// The fixed block of i32 parameters shared by every effect record.
+#[derive(Default, Debug)]
+pub struct Effect {
+    effect_type: EffectType,
+    amount: i32,
+    resource: i32,
+    unit_type: i32,
+    source_player: i32,
+    target_player: i32,
+    technology: i32,
+    object_id: i32,
+    target_object_id: i32,
+    area_x1: i32,
+    area_y1: i32,
+    area_x2: i32,
+    area_y2: i32,
+    text: String,
+}
+
+impl Effect {
+    #[inline]
+    pub fn effect_type(&self) -> EffectType {
+        self.effect_type
+    }
+
+    #[inline]
+    pub fn text<'a>(&'a self) -> &'a str {
+        &self.text
+    }
+
+    fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<Effect> {
+        let mut effect: Effect = Default::default();
+        effect.effect_type = EffectType::from_code(try!(stream.read_i32()));
+        effect.amount = try!(stream.read_i32());
+        effect.resource = try!(stream.read_i32());
+        effect.unit_type = try!(stream.read_i32());
+        effect.source_player = try!(stream.read_i32());
+        effect.target_player = try!(stream.read_i32());
+        effect.technology = try!(stream.read_i32());
+        effect.object_id = try!(stream.read_i32());
+        effect.target_object_id = try!(stream.read_i32());
+        effect.area_x1 = try!(stream.read_i32());
+        effect.area_y1 = try!(stream.read_i32());
+        effect.area_x2 = try!(stream.read_i32());
+        effect.area_y2 = try!(stream.read_i32());
+        effect.text = {
+            let length = try!(stream.read_i32());
+            if length > REASONABLE_STRING_LIMIT as i32 {
+                return Err(ErrorKind::InstructionsTooLarge.into());
+            }
+            if length > 0 {
+                try!(stream.read_sized_str(length as usize))
+            } else {
+                String::new()
+            }
+        };
+        Ok(effect)
+    }
+
+    fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_i32(self.effect_type.as_code()));
+        try!(stream.write_i32(self.amount));
+        try!(stream.write_i32(self.resource));
+        try!(stream.write_i32(self.unit_type));
+        try!(stream.write_i32(self.source_player));
+        try!(stream.write_i32(self.target_player));
+        try!(stream.write_i32(self.technology));
+        try!(stream.write_i32(self.object_id));
+        try!(stream.write_i32(self.target_object_id));
+        try!(stream.write_i32(self.area_x1));
+        try!(stream.write_i32(self.area_y1));
+        try!(stream.write_i32(self.area_x2));
+        try!(stream.write_i32(self.area_y2));
+        try!(stream.write_i32(self.text.len() as i32));
+        try!(stream.write_sized_str(&self.text, self.text.len()));
+        Ok(())
+    }
+}
+
+/// The fixed block of i32 parameters shared by every condition record.
+#[derive(Default, Debug)]
+pub struct Condition {
+    condition_type: ConditionType,
+    amount: i32,
+    resource: i32,
+    object_id: i32,
+    target_object_id: i32,
+    unit_type: i32,
+    player: i32,
+    technology: i32,
+    timer: i32,
+    area_x1: i32,
+    area_y1: i32,
+    area_x2: i32,
+    area_y2: i32,
+}
+
+impl Condition {
+    #[inline]
+    pub fn condition_type(&self) -> ConditionType {
+        self.condition_type
+    }
+
+    fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<Condition> {
+        let mut condition: Condition = Default::default();
+        condition.condition_type = ConditionType::from_code(try!(stream.read_i32()));
+        condition.amount = try!(stream.read_i32());
+        condition.resource = try!(stream.read_i32());
+        condition.object_id = try!(stream.read_i32());
+        condition.target_object_id = try!(stream.read_i32());
+        condition.unit_type = try!(stream.read_i32());
+        condition.player = try!(stream.read_i32());
+        condition.technology = try!(stream.read_i32());
+        condition.timer = try!(stream.read_i32());
+        condition.area_x1 = try!(stream.read_i32());
+        condition.area_y1 = try!(stream.read_i32());
+        condition.area_x2 = try!(stream.read_i32());
+        condition.area_y2 = try!(stream.read_i32());
+        Ok(condition)
+    }
+
+    fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_i32(self.condition_type.as_code()));
+        try!(stream.write_i32(self.amount));
+        try!(stream.write_i32(self.resource));
+        try!(stream.write_i32(self.object_id));
+        try!(stream.write_i32(self.target_object_id));
+        try!(stream.write_i32(self.unit_type));
+        try!(stream.write_i32(self.player));
+        try!(stream.write_i32(self.technology));
+        try!(stream.write_i32(self.timer));
+        try!(stream.write_i32(self.area_x1));
+        try!(stream.write_i32(self.area_y1));
+        try!(stream.write_i32(self.area_x2));
+        try!(stream.write_i32(self.area_y2));
+        Ok(())
+    }
+}
+
+/// A single trigger: its effects fire once all of its conditions are met.
+#[derive(Default, Debug)]
+pub struct Trigger {
+    enabled: bool,
+    looping: bool,
+    description: String,
+    name: String,
+    effects: Vec<Effect>,
+    conditions: Vec<Condition>,
+}
+
+impl Trigger {
+    #[inline]
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    #[inline]
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    #[inline]
+    pub fn name<'a>(&'a self) -> &'a str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn description<'a>(&'a self) -> &'a str {
+        &self.description
+    }
+
+    #[inline]
+    pub fn effects<'a>(&'a self) -> &'a [Effect] {
+        &self.effects
+    }
+
+    #[inline]
+    pub fn conditions<'a>(&'a self) -> &'a [Condition] {
+        &self.conditions
+    }
+
+    fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<Trigger> {
+        let mut trigger: Trigger = Default::default();
+        trigger.enabled = try!(stream.read_u32()) != 0;
+        trigger.looping = try!(stream.read_u32()) != 0;
+        trigger.description = {
+            let length = try!(stream.read_u32()) as usize;
+            if length > REASONABLE_STRING_LIMIT {
+                return Err(ErrorKind::InstructionsTooLarge.into());
+            }
+            try!(stream.read_sized_str(length))
+        };
+        trigger.name = {
+            let length = try!(stream.read_u32()) as usize;
+            if length > REASONABLE_STRING_LIMIT {
+                return Err(ErrorKind::InstructionsTooLarge.into());
+            }
+            try!(stream.read_sized_str(length))
+        };
+
+        let effect_count = try!(stream.read_u32()) as usize;
+        trigger.effects = try!((0..effect_count)
+            .map(|_| Effect::read_from_stream(stream))
+            .collect());
+
+        let condition_count = try!(stream.read_u32()) as usize;
+        trigger.conditions = try!((0..condition_count)
+            .map(|_| Condition::read_from_stream(stream))
+            .collect());
+
+        Ok(trigger)
+    }
+
+    fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_u32(if self.enabled { 1 } else { 0 }));
+        try!(stream.write_u32(if self.looping { 1 } else { 0 }));
+        try!(stream.write_u32(self.description.len() as u32));
+        try!(stream.write_sized_str(&self.description, self.description.len()));
+        try!(stream.write_u32(self.name.len() as u32));
+        try!(stream.write_sized_str(&self.name, self.name.len()));
+
+        try!(stream.write_u32(self.effects.len() as u32));
+        for effect in &self.effects {
+            try!(effect.write_to_stream(stream));
+        }
+
+        try!(stream.write_u32(self.conditions.len() as u32));
+        for condition in &self.conditions {
+            try!(condition.write_to_stream(stream));
+        }
+        Ok(())
+    }
+}
+
+/// The scenario's trigger list, read from the tail of the decompressed body.
+#[derive(Default, Debug)]
+pub struct TriggerSystem {
+    triggers: Vec<Trigger>,
+}
+
+impl TriggerSystem {
+    #[inline]
+    pub fn triggers<'a>(&'a self) -> &'a [Trigger] {
+        &self.triggers
+    }
+
+    pub fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<TriggerSystem> {
+        let mut system: TriggerSystem = Default::default();
+        let trigger_count = try!(stream.read_u32()) as usize;
+        if trigger_count > REASONABLE_TRIGGER_LIMIT {
+            return Err(ErrorKind::TooManyTriggers.into());
+        }
+        system.triggers = try!((0..trigger_count)
+            .map(|_| Trigger::read_from_stream(stream))
+            .collect());
+        Ok(system)
+    }
+
+    pub fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_u32(self.triggers.len() as u32));
+        for trigger in &self.triggers {
+            try!(trigger.write_to_stream(stream));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::Result;
+
+use chariot_io_tools::{ReadExt, WriteExt};
+
+use std::io::prelude::{Read, Write, Seek};
+
+/// A single custom victory requirement attached to a player.
+#[derive(Default, Debug)]
+pub struct CustomVictoryCondition {
+    condition_type: i32,
+    object_id: i32,
+    unit_type: i32,
+    area_x1: i32,
+    area_y1: i32,
+    area_x2: i32,
+    area_y2: i32,
+}
+
+impl CustomVictoryCondition {
+    #[inline]
+    pub fn condition_type(&self) -> i32 {
+        self.condition_type
+    }
+
+    fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<CustomVictoryCondition> {
+        let mut condition: CustomVictoryCondition = Default::default();
+        condition.condition_type = try!(stream.read_i32());
+        condition.object_id = try!(stream.read_i32());
+        condition.unit_type = try!(stream.read_i32());
+        condition.area_x1 = try!(stream.read_i32());
+        condition.area_y1 = try!(stream.read_i32());
+        condition.area_x2 = try!(stream.read_i32());
+        condition.area_y2 = try!(stream.read_i32());
+        Ok(condition)
+    }
+
+    fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_i32(self.condition_type));
+        try!(stream.write_i32(self.object_id));
+        try!(stream.write_i32(self.unit_type));
+        try!(stream.write_i32(self.area_x1));
+        try!(stream.write_i32(self.area_y1));
+        try!(stream.write_i32(self.area_x2));
+        try!(stream.write_i32(self.area_y2));
+        Ok(())
+    }
+}
+
+/// The standard and custom win requirements for a single player.
+#[derive(Default, Debug)]
+pub struct PlayerVictory {
+    conquest: bool,
+    ruins: i32,
+    artifacts: i32,
+    discoveries: i32,
+    exploration_percent: i32,
+    gold: i32,
+    all_conditions_required: bool,
+    custom_conditions: Vec<CustomVictoryCondition>,
+}
+
+impl PlayerVictory {
+    #[inline]
+    pub fn conquest(&self) -> bool {
+        self.conquest
+    }
+
+    #[inline]
+    pub fn gold(&self) -> i32 {
+        self.gold
+    }
+
+    #[inline]
+    pub fn exploration_percent(&self) -> i32 {
+        self.exploration_percent
+    }
+
+    #[inline]
+    pub fn all_conditions_required(&self) -> bool {
+        self.all_conditions_required
+    }
+
+    #[inline]
+    pub fn custom_conditions<'a>(&'a self) -> &'a [CustomVictoryCondition] {
+        &self.custom_conditions
+    }
+
+    fn read_from_stream<S: Read + Seek>(stream: &mut S) -> Result<PlayerVictory> {
+        let mut victory: PlayerVictory = Default::default();
+        victory.conquest = try!(stream.read_u32()) != 0;
+        victory.ruins = try!(stream.read_i32());
+        victory.artifacts = try!(stream.read_i32());
+        victory.discoveries = try!(stream.read_i32());
+        victory.exploration_percent = try!(stream.read_i32());
+        victory.gold = try!(stream.read_i32());
+        victory.all_conditions_required = try!(stream.read_u32()) != 0;
+
+        let condition_count = try!(stream.read_u32()) as usize;
+        victory.custom_conditions = try!((0..condition_count)
+            .map(|_| CustomVictoryCondition::read_from_stream(stream))
+            .collect());
+        Ok(victory)
+    }
+
+    fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_u32(if self.conquest { 1 } else { 0 }));
+        try!(stream.write_i32(self.ruins));
+        try!(stream.write_i32(self.artifacts));
+        try!(stream.write_i32(self.discoveries));
+        try!(stream.write_i32(self.exploration_percent));
+        try!(stream.write_i32(self.gold));
+        try!(stream.write_u32(if self.all_conditions_required { 1 } else { 0 }));
+
+        try!(stream.write_u32(self.custom_conditions.len() as u32));
+        for condition in &self.custom_conditions {
+            try!(condition.write_to_stream(stream));
+        }
+        Ok(())
+    }
+}
+
+/// The scenario-wide victory settings, read after the player unit groups.
+#[derive(Default, Debug)]
+pub struct VictoryConditions {
+    mode: u8,
+    players: Vec<PlayerVictory>,
+}
+
+impl VictoryConditions {
+    /// Returns the victory settings for the given player, if present
+    #[inline]
+    pub fn player<'a>(&'a self, player_id: usize) -> Option<&'a PlayerVictory> {
+        self.players.get(player_id)
+    }
+
+    pub fn read_from_stream<S: Read + Seek>(stream: &mut S,
+                                            player_count: usize)
+                                            -> Result<VictoryConditions> {
+        let mut conditions: VictoryConditions = Default::default();
+        conditions.mode = try!(stream.read_byte());
+        conditions.players = try!((0..player_count)
+            .map(|_| PlayerVictory::read_from_stream(stream))
+            .collect());
+        Ok(conditions)
+    }
+
+    pub fn write_to_stream<W: Write + Seek>(&self, stream: &mut W) -> Result<()> {
+        try!(stream.write_byte(self.mode));
+        for player in &self.players {
+            try!(player.write_to_stream(stream));
+        }
+        Ok(())
+    }
+}